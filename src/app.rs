@@ -1,20 +1,99 @@
-use crate::text_input::{SrcInput, DstInput};
-use web_sys::{Request, RequestInit, RequestMode, Response};
+use crate::text_input::{SrcInput, DstInput, PresetSelect, WaypointInput};
+use crate::map;
+use crate::toast::Toast;
+use web_sys::{
+    AbortController, AbortSignal, Blob, BlobPropertyBag, Headers, HtmlAnchorElement,
+    DragEvent, HtmlInputElement, KeyboardEvent, Request, RequestInit, RequestMode, Response, Url, UrlSearchParams,
+};
 use yew::prelude::*;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
+use gloo_timers::callback::Timeout;
+use gloo_timers::future::TimeoutFuture;
+use futures::future::{select, Either};
+use gloo_events::EventListener;
 use serde::{Serialize, Deserialize};
 use std::{
     error::Error,
     fmt::{self, Debug, Display, Formatter},
 };
 
+/// Decimal places shown for distances unless the user picks otherwise.
+const DEFAULT_PRECISION: i32 = 6;
+
+/// Most decimal places the precision slider allows.
+const MAX_PRECISION: i32 = 8;
+
+/// Meters in one kilometer, the unit the distance service answers in.
+const METERS_PER_KM: f64 = 1000.0;
+
+/// Meters in one statute mile.
+const METERS_PER_MILE: f64 = 1609.344;
+
+/// How long a freshly arrived distance stays highlighted.
+const HIGHLIGHT_MS: u32 = 1_000;
+
+/// Number of recent results kept for the history chart.
+const HISTORY_LEN: usize = 10;
+
+/// Size of the history chart in SVG user units.
+const CHART_WIDTH: f64 = 300.0;
+const CHART_HEIGHT: f64 = 60.0;
+
+/// Id of the element the route map is drawn into.
+const MAP_ID: &str = "map";
+
+/// How long a toast stays on screen.
+const TOAST_MS: u32 = 3_000;
+
+/// How long an exported file's object URL is kept alive after the download starts.
+const REVOKE_DELAY_MS: u32 = 1_000;
+
+/// `localStorage` key holding the chosen theme.
+const THEME_STORAGE_KEY: &str = "theme";
+
+/// Endpoint of the distance service.
+const DISTANCE_URL: &str = "http://localhost:5000/distance";
+
+/// How many times a distance fetch is attempted before giving up.
+const DEFAULT_FETCH_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled for every retry after it.
+const RETRY_BASE_DELAY_MS: u32 = 500;
+
+/// How long a single distance request may take before it is abandoned.
+const DEFAULT_FETCH_TIMEOUT_MS: u32 = 10_000;
+
 pub enum Msg {
     SetSrc(String),
     SetDst(String),
+    PasteSrc(String),
+    PasteDst(String),
+    SetSrcPart(usize, String),
+    SetDstPart(usize, String),
+    ToggleEntryMode,
+    ToggleOrder,
+    AddWaypoint,
+    RemoveWaypoint(usize),
+    SetWaypoint(usize, String),
+    SetSpeed(f64),
+    SetPrecision(i32),
+    ToggleMiles,
+    ToggleInfo,
+    ToggleNormalize,
     GetDistance,
-    SetDistanceFetchState(FetchState<f64>),
+    Swap,
+    Reset,
+    ToggleTheme,
+    Export,
+    CopyLink,
+    CopyPermalink,
+    SetDistanceFetchState(FetchState<Route>),
+    ClearHighlight,
+    ShowToast(String),
+    DismissToast,
+    MapUnavailable,
 }
 
 
@@ -24,6 +103,29 @@ pub struct Coordinate {
     pub lng: f64,
 }
 
+impl Display for Coordinate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.lat, self.lng)
+    }
+}
+
+/// JSON body sent to the distance service.
+#[derive(Debug, Serialize)]
+pub struct DistanceRequest<'a> {
+    pub src: &'a str,
+    pub dst: &'a str,
+}
+
+/// A computed route as written to an exported file.
+#[derive(Debug, Serialize)]
+pub struct Export<'a> {
+    pub src: &'a str,
+    pub waypoints: &'a [String],
+    pub dst: &'a str,
+    pub distance: f64,
+    pub unit: &'a str,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Data {
     pub data: InnerData
@@ -37,89 +139,847 @@ pub struct InnerData {
     pub src: Coordinate,
 }
 
+impl InnerData {
+    /// The distance in meters; the service answers in km.
+    pub fn meters(&self) -> f64 {
+        self.distance * METERS_PER_KM
+    }
+}
+
+
+/// A computed route: the stops it was requested for, the service reply for every
+/// leg and the total length.
+pub struct Route {
+    pub stops: Vec<String>,
+    /// Empty when the route was restored from a permalink rather than fetched.
+    pub legs: Vec<InnerData>,
+    pub meters: f64,
+}
+
+impl Route {
+    fn new(stops: Vec<String>, legs: Vec<InnerData>) -> Self {
+        let meters = legs.iter().map(InnerData::meters).sum();
+        Self { stops, legs, meters }
+    }
+}
 
 /// Something wrong has occurred while fetching an external resource.
 #[derive(Debug, Clone, PartialEq)]
-pub struct FetchError {
-    err: JsValue,
+pub enum FetchError {
+    /// The request failed with the given JS error.
+    Js(JsValue),
+    /// No response arrived before the timeout elapsed.
+    Timeout,
+    /// The request was superseded by a newer one and aborted.
+    Aborted,
+    /// The service answered with something that could not be understood.
+    InvalidResponse(String),
 }
 impl Display for FetchError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        Debug::fmt(&self.err, f)
+        match self {
+            FetchError::Js(err) => Debug::fmt(err, f),
+            FetchError::Timeout => write!(f, "request timed out"),
+            FetchError::Aborted => write!(f, "request aborted"),
+            FetchError::InvalidResponse(err) => write!(f, "invalid response: {}", err),
+        }
     }
 }
 impl Error for FetchError {}
 
+impl FetchError {
+    /// Whether the request never reached the service, e.g. it is down or blocks CORS.
+    fn is_network_error(&self) -> bool {
+        match self {
+            FetchError::Js(err) => err.dyn_ref::<js_sys::Error>().is_some_and(|err| {
+                let message = String::from(err.message());
+                ["Failed to fetch", "NetworkError", "Load failed"]
+                    .iter()
+                    .any(|needle| message.contains(needle))
+            }),
+            _ => false,
+        }
+    }
+
+    /// Whether trying again might help; a malformed reply won't fix itself.
+    fn is_transient(&self) -> bool {
+        matches!(self, FetchError::Js(_) | FetchError::Timeout)
+    }
+
+    /// Message shown in the readout when the fetch failed.
+    fn user_message(&self) -> String {
+        match self {
+            _ if self.is_network_error() => {
+                "Couldn't reach the distance service — is it running?".to_string()
+            }
+            FetchError::Timeout => "The distance service timed out, please try again.".to_string(),
+            _ => format!("Something went wrong: {}", self),
+        }
+    }
+}
+
 impl From<JsValue> for FetchError {
     fn from(value: JsValue) -> Self {
-        Self { err: value }
+        Self::Js(value)
+    }
+}
+
+/// Colour scheme applied to the root `<main>` element.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    fn class(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    /// The theme saved on a previous visit, falling back to the system preference.
+    fn load() -> Self {
+        let window = gloo_utils::window();
+        let stored = window
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|storage| storage.get_item(THEME_STORAGE_KEY).ok().flatten());
+        match stored.as_deref() {
+            Some("dark") => Theme::Dark,
+            Some("light") => Theme::Light,
+            _ => {
+                let prefers_dark = window
+                    .match_media("(prefers-color-scheme: dark)")
+                    .ok()
+                    .flatten()
+                    .is_some_and(|query| query.matches());
+                if prefers_dark {
+                    Theme::Dark
+                } else {
+                    Theme::Light
+                }
+            }
+        }
+    }
+
+    fn store(self) {
+        if let Ok(Some(storage)) = gloo_utils::window().local_storage() {
+            // Persisting is best effort, e.g. storage may be disabled.
+            let _ = storage.set_item(THEME_STORAGE_KEY, self.class());
+        }
     }
 }
 
+/// A past successful calculation.
+pub struct HistoryEntry {
+    pub stops: Vec<String>,
+    pub meters: f64,
+}
+
 pub struct App {
     src: String,
     dst: String,
-    distance: FetchState<f64>,
+    waypoints: Vec<String>,
+    speed: f64,
+    precision: i32,
+    show_miles: bool,
+    show_info: bool,
+    /// Whether the coordinate inputs tidy values as they are typed.
+    normalize: bool,
+    /// Whether SRC and DST are entered as separate lat and lng number boxes.
+    numeric_entry: bool,
+    /// Whether coordinates are entered as "lng,lat" instead of "lat,lng".
+    lng_lat: bool,
+    fetch_attempts: u32,
+    fetch_timeout_ms: u32,
+    abort: Option<AbortController>,
+    /// Stops of the latest request, cleared whenever an input changes.
+    last_request: Option<Vec<String>>,
+    /// Set when an input changes after a distance was shown, so it no longer matches.
+    dirty: bool,
+    theme: Theme,
+    /// Global keyboard shortcut listener, removed when dropped.
+    keydown: Option<EventListener>,
+    /// Set briefly after a new distance arrives to highlight the readout.
+    just_updated: bool,
+    /// Pending timer that clears `just_updated`, cancelled when dropped.
+    highlight: Option<Timeout>,
+    toast: Option<String>,
+    /// Pending timer that dismisses the toast, cancelled when replaced or dropped.
+    toast_timer: Option<Timeout>,
+    /// Set when a new route needs drawing on the map after the next render.
+    map_outdated: bool,
+    /// Set once the map library failed, which hides the map for good.
+    map_unavailable: bool,
+    /// Most recent results, oldest first.
+    history: Vec<HistoryEntry>,
+    distance: FetchState<Route>,
 }
 
 /// The possible states a fetch request can be in.
 pub enum FetchState<T> {
     NotFetching,
-    Fetching,
+    /// In flight, on the given attempt counting from 1.
+    Fetching { attempt: u32 },
     Success(T),
     Failed(FetchError),
 }
 
-async fn fetch_distance(url: String) -> Result<f64, FetchError> {
+async fn fetch_distance(
+    src: &str,
+    dst: &str,
+    timeout_ms: u32,
+    signal: Option<&AbortSignal>,
+) -> Result<InnerData, FetchError> {
+    if signal.is_some_and(AbortSignal::aborted) {
+        return Err(FetchError::Aborted);
+    }
+    // A controller of its own lets a timed out attempt be cancelled without aborting
+    // the whole submit, while aborting the submit still cancels this attempt.
+    let controller = AbortController::new()?;
+    let _forward_abort = signal.map(|signal| {
+        let controller = controller.clone();
+        EventListener::once(signal, "abort", move |_| controller.abort())
+    });
+    let attempt_signal = controller.signal();
+    let request = Box::pin(request_distance(src, dst, Some(&attempt_signal)));
+    let timeout = Box::pin(TimeoutFuture::new(timeout_ms));
+    match select(request, timeout).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => {
+            controller.abort();
+            Err(FetchError::Timeout)
+        }
+    }
+}
+
+async fn request_distance(
+    src: &str,
+    dst: &str,
+    signal: Option<&AbortSignal>,
+) -> Result<InnerData, FetchError> {
+    let body = serde_json::to_string(&DistanceRequest { src, dst })
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let headers = Headers::new()?;
+    headers.set("Content-Type", "application/json")?;
+
     let mut opts = RequestInit::new();
     opts.method("POST");
     opts.mode(RequestMode::Cors);
+    opts.headers(&headers);
+    opts.body(Some(&JsValue::from_str(&body)));
+    opts.signal(signal);
 
-    let request = Request::new_with_str_and_init(&url, &opts)?;
+    let request = Request::new_with_str_and_init(DISTANCE_URL, &opts)?;
 
     let window = gloo_utils::window();
     let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-    let resp: Response = resp_value.dyn_into().unwrap();
+    let resp: Response = resp_value
+        .dyn_into()
+        .map_err(|_| FetchError::InvalidResponse("not a fetch Response".to_string()))?;
 
     let json = JsFuture::from(resp.json()?).await?;
 
-    let data: Data = json.into_serde().unwrap();
-    Ok(data.data.distance)
+    let data: Data = json
+        .into_serde()
+        .map_err(|err| FetchError::InvalidResponse(err.to_string()))?;
+    Ok(data.data)
+}
+
+/// Fetch every consecutive leg along the given stops. A leg failing with a transient
+/// error is retried with exponential backoff, up to `attempts` tries, and `on_attempt`
+/// is told whenever the attempt number of the leg being fetched changes.
+async fn fetch_route(
+    stops: Vec<String>,
+    attempts: u32,
+    timeout_ms: u32,
+    signal: Option<&AbortSignal>,
+    on_attempt: impl Fn(u32),
+) -> Result<Vec<InnerData>, FetchError> {
+    let mut legs = Vec::with_capacity(stops.len().saturating_sub(1));
+    let mut shown = 1;
+    for leg in stops.windows(2) {
+        let mut attempt = 1;
+        let data = loop {
+            if attempt != shown {
+                on_attempt(attempt);
+                shown = attempt;
+            }
+            match fetch_distance(&leg[0], &leg[1], timeout_ms, signal).await {
+                Err(_) if signal.is_some_and(AbortSignal::aborted) => return Err(FetchError::Aborted),
+                Err(err) if err.is_transient() && attempt < attempts => {
+                    TimeoutFuture::new(RETRY_BASE_DELAY_MS << (attempt - 1)).await;
+                    if signal.is_some_and(AbortSignal::aborted) {
+                        return Err(FetchError::Aborted);
+                    }
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        };
+        legs.push(data);
+    }
+    Ok(legs)
+}
+
+/// Format `value` with the digit grouping of the browser locale, if `Intl` is available.
+fn format_locale(value: f64, decimals: i32) -> Option<String> {
+    if !js_sys::Reflect::has(&js_sys::global(), &"Intl".into()).unwrap_or(false) {
+        return None;
+    }
+    let options = js_sys::Object::new();
+    let decimals = JsValue::from(decimals);
+    js_sys::Reflect::set(&options, &"minimumFractionDigits".into(), &decimals).ok()?;
+    js_sys::Reflect::set(&options, &"maximumFractionDigits".into(), &decimals).ok()?;
+    let formatter = js_sys::Intl::NumberFormat::new(&js_sys::Array::new(), &options);
+    formatter
+        .format()
+        .call1(&JsValue::NULL, &value.into())
+        .ok()?
+        .as_string()
+}
+
+/// Hand `contents` to the browser as a JSON file download called `filename`.
+fn download_json(filename: &str, contents: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = BlobPropertyBag::new();
+    options.type_("application/json");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor: HtmlAnchorElement = gloo_utils::document()
+        .create_element("a")?
+        .dyn_into()
+        .map_err(JsValue::from)?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    // Revoking right away can cancel the download in some browsers.
+    Timeout::new(REVOKE_DELAY_MS, move || {
+        if let Err(err) = Url::revoke_object_url(&url) {
+            web_sys::console::error_1(&err);
+        }
+    })
+    .forget();
+    Ok(())
+}
+
+/// Query parameters of the page URL, used to prefill the inputs.
+fn url_params() -> Option<UrlSearchParams> {
+    let search = gloo_utils::window().location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()
+}
+
+/// Percent-encode a query value, including the "," and "-" found in coordinates.
+fn encode_param(value: &str) -> String {
+    String::from(js_sys::encode_uri_component(value)).replace('-', "%2D")
+}
+
+/// Write `text` to the clipboard.
+async fn copy_to_clipboard(text: String) -> Result<(), JsValue> {
+    let navigator = gloo_utils::window().navigator();
+    let clipboard = js_sys::Reflect::get(&navigator, &"clipboard".into())?;
+    let write_text: js_sys::Function =
+        js_sys::Reflect::get(&clipboard, &"writeText".into())?.dyn_into()?;
+    let promise: js_sys::Promise = write_text.call1(&clipboard, &text.into())?.dyn_into()?;
+    JsFuture::from(promise).await?;
+    Ok(())
+}
+
+/// Listen for Ctrl+Enter to submit and Alt+S to swap SRC and DST.
+/// Both need a modifier, so plain typing in an input never triggers them.
+fn keyboard_shortcuts(ctx: &Context<App>) -> EventListener {
+    let link = ctx.link().clone();
+    EventListener::new(&gloo_utils::window(), "keydown", move |event| {
+        let event = match event.dyn_ref::<KeyboardEvent>() {
+            Some(event) => event,
+            None => return,
+        };
+        if event.ctrl_key() && event.key() == "Enter" {
+            event.prevent_default();
+            link.send_message(Msg::GetDistance);
+        } else if event.alt_key() && event.code() == "KeyS" {
+            event.prevent_default();
+            link.send_message(Msg::Swap);
+        }
+    })
+}
+
+/// Whether a drag carries files, as opposed to e.g. text dragged into an input.
+fn drags_files(event: &DragEvent) -> bool {
+    event
+        .data_transfer()
+        .is_some_and(|transfer| transfer.types().includes(&"Files".into(), 0))
+}
+
+/// Read a dropped text file and fill SRC from its first line and DST from its second.
+/// Other drops are left to the browser.
+fn load_dropped_file(ctx: &Context<App>) -> Callback<DragEvent> {
+    let link = ctx.link().clone();
+    Callback::from(move |event: DragEvent| {
+        if !drags_files(&event) {
+            return;
+        }
+        event.prevent_default();
+        let file = event
+            .data_transfer()
+            .and_then(|transfer| transfer.files())
+            .and_then(|files| files.get(0));
+        if let Some(file) = file {
+            link.send_future_batch(async move {
+                let text = match JsFuture::from(file.text()).await {
+                    Ok(text) => text.as_string().unwrap_or_default(),
+                    Err(err) => {
+                        web_sys::console::error_1(&err);
+                        return Vec::new();
+                    }
+                };
+                let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+                let mut msgs = Vec::new();
+                if let Some(src) = lines.next() {
+                    msgs.push(Msg::SetSrc(src.to_string()));
+                }
+                if let Some(dst) = lines.next() {
+                    msgs.push(Msg::SetDst(dst.to_string()));
+                }
+                msgs
+            });
+        }
+    })
+}
+
+/// Rewrite a "lat lng" or "lat; lng" pair into the "lat,lng" form the service expects,
+/// swapping the parts when `lng_lat` says they were entered in GeoJSON order.
+/// Anything else, such as an H3 index, is only trimmed.
+fn normalize_location(input: &str, lng_lat: bool) -> String {
+    let input = input.trim();
+    let parts: Vec<&str> = if input.contains(',') {
+        input.split(',').map(str::trim).collect()
+    } else {
+        input
+            .split(|c: char| c == ';' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .collect()
+    };
+    match parts.as_slice() {
+        [lng, lat] if lng_lat => format!("{},{}", lat, lng),
+        [lat, lng] => format!("{},{}", lat, lng),
+        _ => input.to_string(),
+    }
+}
+
+/// All stops in route order, normalized into the form sent to the service.
+fn route_stops(src: &str, waypoints: &[String], dst: &str, lng_lat: bool) -> Vec<String> {
+    let mut stops = Vec::with_capacity(waypoints.len() + 2);
+    stops.push(normalize_location(src, lng_lat));
+    stops.extend(waypoints.iter().map(|wp| normalize_location(wp, lng_lat)));
+    stops.push(normalize_location(dst, lng_lat));
+    stops
+}
+
+/// Split a pasted "lat,lng lat,lng" line into its SRC and DST halves.
+/// Only fires when exactly four numbers are present.
+fn split_pair(input: &str) -> Option<(String, String)> {
+    let parts: Vec<&str> = input
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+    match parts.as_slice() {
+        [lat1, lng1, lat2, lng2] if parts.iter().all(|part| part.parse::<f64>().is_ok()) => {
+            Some((format!("{},{}", lat1, lng1), format!("{},{}", lat2, lng2)))
+        }
+        _ => None,
+    }
+}
+
+/// The `index`-th comma separated part of a "lat,lng" string, or "" if missing.
+fn coordinate_part(value: &str, index: usize) -> String {
+    value.split(',').nth(index).map(str::trim).unwrap_or("").to_string()
+}
+
+/// Replace the lat (0) or lng (1) part of a "lat,lng" string.
+fn with_coordinate_part(value: &str, index: usize, part: String) -> String {
+    let mut parts = [coordinate_part(value, 0), coordinate_part(value, 1)];
+    parts[index] = part;
+    if parts.iter().all(String::is_empty) {
+        String::new()
+    } else {
+        parts.join(",")
+    }
 }
 
 impl App {
-    fn get_distance(&self) -> Option<String> {
+    fn inputs_changed(&mut self) {
+        self.last_request = None;
+        self.dirty = true;
+    }
+
+    /// Whether the shown distance came from a permalink and was not fetched.
+    fn is_from_permalink(&self) -> bool {
+        matches!(&self.distance, FetchState::Success(route) if route.legs.is_empty())
+    }
+
+    /// Whether the shown distance no longer matches the inputs.
+    fn is_stale(&self) -> bool {
+        self.dirty && matches!(self.distance, FetchState::Success(_))
+    }
+
+    /// Fill SRC and DST from a pasted "lat,lng lat,lng" line, returning whether it was one.
+    fn paste_pair(&mut self, value: &str) -> bool {
+        match split_pair(value) {
+            Some((src, dst)) => {
+                self.src = src;
+                self.dst = dst;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All stops in route order: SRC, any waypoints, then DST.
+    fn stops(&self) -> Vec<String> {
+        route_stops(&self.src, &self.waypoints, &self.dst, self.lng_lat)
+    }
+
+    /// Link to this page that prefills the current inputs, and with `with_result`
+    /// also the computed distance so it shows without recomputing.
+    fn share_link(&self, with_result: bool) -> Option<String> {
+        let location = gloo_utils::window().location();
+        let mut link = format!(
+            "{}{}?src={}",
+            location.origin().ok()?,
+            location.pathname().ok()?,
+            encode_param(&self.src)
+        );
+        for wp in &self.waypoints {
+            link.push_str(&format!("&wp={}", encode_param(wp)));
+        }
+        link.push_str(&format!("&dst={}", encode_param(&self.dst)));
+        if self.lng_lat {
+            link.push_str("&order=lnglat");
+        }
+        if with_result {
+            // A stale distance was computed for other coordinates than those linked.
+            if self.is_stale() {
+                return None;
+            }
+            let km = self.total_distance()? / METERS_PER_KM;
+            link.push_str(&format!("&d={:.*}&u=km", self.precision as usize, km));
+        }
+        Some(link)
+    }
+
+    /// Total route length in meters, once it has been fetched.
+    fn total_distance(&self) -> Option<f64> {
         match &self.distance {
             FetchState::NotFetching => None,
-            FetchState::Fetching => None,
-            FetchState::Success(dist) => Some(format!("Distance = {} Km.", dist)),
+            FetchState::Fetching { .. } => None,
+            FetchState::Success(route) => Some(route.meters),
             FetchState::Failed(_) => None
         }
     }
 
+    fn format_number(&self, value: f64) -> String {
+        format_locale(value, self.precision)
+            .unwrap_or_else(|| format!("{:.*}", self.precision as usize, value))
+    }
+
+    /// Render a distance given in meters as km, followed by miles when that mode is on.
+    fn format_distance(&self, meters: f64) -> String {
+        let km = self.format_number(meters / METERS_PER_KM);
+        if self.show_miles {
+            format!("{} km ({} mi)", km, self.format_number(meters / METERS_PER_MILE))
+        } else {
+            format!("{} Km.", km)
+        }
+    }
+
+    fn get_distance(&self) -> Option<String> {
+        self.total_distance().map(|dist| format!("Distance = {}", self.format_distance(dist)))
+    }
+
+    /// Estimated travel time at the entered speed, hidden when no speed is set.
+    fn eta_text(&self) -> Option<String> {
+        if self.speed <= 0.0 {
+            return None;
+        }
+        let km = self.total_distance()? / METERS_PER_KM;
+        let minutes = (km / self.speed * 60.0).round() as u64;
+        Some(format!("ETA = {}h {}m", minutes / 60, minutes % 60))
+    }
+
     fn row_text(&self) -> String {
         if self.src.is_empty() {
             "Please provide SRC".to_string()
+        } else if let Some(i) = self.waypoints.iter().position(|wp| wp.is_empty()) {
+            format!("Please provide waypoint {}", i + 1)
         } else if self.dst.is_empty() {
             "Please provide Dst".to_string()
+        } else if let FetchState::Failed(err) = &self.distance {
+            err.user_message()
+        } else if let FetchState::Fetching { attempt } = self.distance {
+            if attempt > 1 {
+                format!("Retrying… ({}/{})", attempt, self.fetch_attempts)
+            } else {
+                "Calculating…".to_string()
+            }
         } else {
             match self.get_distance() {
                 None => "Click Submit...".to_string(),
+                Some(val) if self.is_stale() => format!("{} (stale — click Submit)", val),
+                Some(val) if self.is_from_permalink() => {
+                    format!("{} (from link — click Submit to verify)", val)
+                }
                 Some(val) => val
             }
         }
     }
+
+    /// The coordinates the service resolved each stop to, with per-leg distances
+    /// when the route has more than one leg.
+    fn view_legs(&self) -> Html {
+        match &self.distance {
+            FetchState::Success(route) if route.legs.len() == 1 => html! {
+                <div class="legs">
+                    {format!("Resolved: {} → {}", route.legs[0].src, route.legs[0].dst)}
+                </div>
+            },
+            FetchState::Success(route) if !route.legs.is_empty() => html! {
+                <ul class="legs">
+                    { for route.legs.iter().enumerate().map(|(i, leg)| html! {
+                        <li>
+                            {format!(
+                                "Leg {}: {} → {}: {}",
+                                i + 1,
+                                leg.src,
+                                leg.dst,
+                                self.format_distance(leg.meters())
+                            )}
+                        </li>
+                    }) }
+                </ul>
+            },
+            _ => html! {},
+        }
+    }
+
+    fn view_info(&self) -> Html {
+        if !self.show_info {
+            return html! {};
+        }
+        html! {
+            <div class="info footnote">
+                <p>
+                    {"Vincenty's inverse method finds the shortest path between two points on \
+                      the WGS84 ellipsoid by iterating on the longitude difference until it \
+                      converges."}
+                </p>
+                <p>
+                    {"When it converges the result is accurate to well under a millimeter on \
+                      the ellipsoid, far better than the spherical Haversine formula."}
+                </p>
+                <p>
+                    {"For nearly antipodal points the iteration may fail to converge, in \
+                      which case no distance can be given."}
+                </p>
+            </div>
+        }
+    }
+
+    /// Separate lat and lng number boxes editing a "lat,lng" string.
+    fn view_numeric(
+        &self,
+        ctx: &Context<Self>,
+        value: &str,
+        label: &str,
+        to_msg: fn(usize, String) -> Msg,
+    ) -> Html {
+        let part_input = |index: usize, name: &str| {
+            let oninput = ctx.link().callback(move |e: InputEvent| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                to_msg(index, input.value())
+            });
+            html! {
+                <input
+                    type="number"
+                    step="any"
+                    placeholder={name.to_string()}
+                    value={coordinate_part(value, index)}
+                    {oninput}
+                    aria-label={format!("{} {}", label, name)}
+                />
+            }
+        };
+        html! {
+            <div class="numeric">
+                { if self.lng_lat {
+                    html! { <>{part_input(0, "longitude")}{part_input(1, "latitude")}</> }
+                } else {
+                    html! { <>{part_input(0, "latitude")}{part_input(1, "longitude")}</> }
+                } }
+            </div>
+        }
+    }
+
+    /// Container for the route map, hidden unless a fetched route is shown. It stays
+    /// mounted across fetches so the same Leaflet map is reused for every route.
+    fn view_map(&self) -> Html {
+        if self.map_unavailable {
+            return html! {};
+        }
+        let shown = matches!(&self.distance, FetchState::Success(route) if !route.legs.is_empty());
+        html! {
+            <div
+                id={MAP_ID}
+                class={classes!("map", (!shown).then_some("hidden"))}
+                role="img"
+                aria-label="Map of the route"
+            ></div>
+        }
+    }
+
+    /// Bars of the recent distances, scaled to the longest one.
+    fn view_history_chart(&self) -> Html {
+        let max = self.history.iter().map(|entry| entry.meters).fold(0.0, f64::max);
+        if max <= 0.0 {
+            return html! {};
+        }
+        let slot = CHART_WIDTH / HISTORY_LEN as f64;
+        html! {
+            <svg
+                class="history"
+                viewBox={format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT)}
+                role="img"
+                aria-label="Recent distances"
+            >
+                { for self.history.iter().enumerate().map(|(i, entry)| {
+                    let height = entry.meters / max * CHART_HEIGHT;
+                    html! {
+                        <rect
+                            x={(i as f64 * slot).to_string()}
+                            y={(CHART_HEIGHT - height).to_string()}
+                            width={(slot * 0.8).to_string()}
+                            height={height.to_string()}
+                        >
+                            <title>
+                                {format!("{}: {}", entry.stops.join(" → "), self.format_distance(entry.meters))}
+                            </title>
+                        </rect>
+                    }
+                }) }
+            </svg>
+        }
+    }
+
+    /// How a coordinate pair is written in the chosen order, for labels.
+    fn pair_order(&self) -> &'static str {
+        if self.lng_lat {
+            "lng, lat"
+        } else {
+            "lat, lng"
+        }
+    }
+
+    /// An example "lat,lng" pair written in the chosen order.
+    fn example_pair(&self, lat: &str, lng: &str) -> String {
+        if self.lng_lat {
+            format!("{},{}", lng, lat)
+        } else {
+            format!("{},{}", lat, lng)
+        }
+    }
+
+    fn view_waypoint(&self, ctx: &Context<Self>, i: usize, value: &str) -> Html {
+        let on_change = ctx.link().callback(move |wp: String| Msg::SetWaypoint(i, wp));
+        html! {
+            <>
+                <div>
+                    {format!("Enter waypoint {} ({}) or H3 index:", i + 1, self.pair_order())}
+                </div>
+                <div class="waypoint">
+                    <WaypointInput
+                        {on_change}
+                        value={value.to_string()}
+                        aria_label={format!("Waypoint {} coordinate", i + 1)}
+                        normalize={self.normalize}
+                    />
+                    <button
+                        onclick={ctx.link().callback(move |_| Msg::RemoveWaypoint(i))}
+                        aria-label={format!("Remove waypoint {}", i + 1)}
+                    >
+                        { "Remove" }
+                    </button>
+                </div>
+                <br/>
+            </>
+        }
+    }
 }
 
 impl Component for App {
     type Message = Msg;
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
+    fn create(ctx: &Context<Self>) -> Self {
+        let params = url_params();
+        let param = |name: &str| params.as_ref().and_then(|params| params.get(name));
+        let src = param("src").unwrap_or_default();
+        let dst = param("dst").unwrap_or_default();
+        let waypoints: Vec<String> = params
+            .as_ref()
+            .map(|params| params.get_all("wp").iter().filter_map(|wp| wp.as_string()).collect())
+            .unwrap_or_default();
+        let lng_lat = param("order").as_deref() == Some("lnglat");
+        // A permalink may carry a precomputed distance in km; only trust a sane number.
+        let linked_km = param("d")
+            .filter(|_| param("u").is_none_or(|unit| unit == "km"))
+            .and_then(|d| d.parse::<f64>().ok())
+            .filter(|km| km.is_finite() && *km >= 0.0);
+        let distance = match linked_km {
+            Some(km) => FetchState::Success(Route {
+                stops: route_stops(&src, &waypoints, &dst, lng_lat),
+                legs: Vec::new(),
+                meters: km * METERS_PER_KM,
+            }),
+            None => FetchState::NotFetching,
+        };
         Self {
-            src: "".to_string(),
-            dst: "".to_string(),
-            distance: FetchState::NotFetching,
+            src,
+            dst,
+            waypoints,
+            speed: 0.0,
+            precision: DEFAULT_PRECISION,
+            show_miles: false,
+            show_info: false,
+            normalize: false,
+            numeric_entry: false,
+            lng_lat,
+            fetch_attempts: DEFAULT_FETCH_ATTEMPTS,
+            fetch_timeout_ms: DEFAULT_FETCH_TIMEOUT_MS,
+            abort: None,
+            last_request: None,
+            dirty: false,
+            theme: Theme::load(),
+            keydown: Some(keyboard_shortcuts(ctx)),
+            just_updated: false,
+            highlight: None,
+            toast: None,
+            toast_timer: None,
+            map_outdated: false,
+            map_unavailable: false,
+            history: Vec::new(),
+            distance,
         }
     }
 
@@ -127,72 +987,554 @@ impl Component for App {
         match msg {
             Msg::SetSrc(src) => {
                 self.src = src;
+                self.inputs_changed();
                 true
             }
             Msg::SetDst(dst) => {
                 self.dst = dst;
+                self.inputs_changed();
+                true
+            }
+            Msg::PasteSrc(src) => {
+                if !self.paste_pair(&src) {
+                    self.src = src;
+                }
+                self.inputs_changed();
+                true
+            }
+            Msg::PasteDst(dst) => {
+                if !self.paste_pair(&dst) {
+                    self.dst = dst;
+                }
+                self.inputs_changed();
+                true
+            }
+            Msg::SetSrcPart(index, part) => {
+                let src = with_coordinate_part(&self.src, index, part);
+                self.update(ctx, Msg::SetSrc(src))
+            }
+            Msg::SetDstPart(index, part) => {
+                let dst = with_coordinate_part(&self.dst, index, part);
+                self.update(ctx, Msg::SetDst(dst))
+            }
+            Msg::ToggleEntryMode => {
+                self.numeric_entry = !self.numeric_entry;
+                true
+            }
+            Msg::ToggleOrder => {
+                self.lng_lat = !self.lng_lat;
+                self.inputs_changed();
+                true
+            }
+            Msg::AddWaypoint => {
+                self.waypoints.push("".to_string());
+                self.inputs_changed();
+                true
+            }
+            Msg::RemoveWaypoint(i) => {
+                if i < self.waypoints.len() {
+                    self.waypoints.remove(i);
+                }
+                self.inputs_changed();
+                true
+            }
+            Msg::SetWaypoint(i, wp) => {
+                if let Some(slot) = self.waypoints.get_mut(i) {
+                    *slot = wp;
+                }
+                self.inputs_changed();
+                true
+            }
+            Msg::SetSpeed(speed) => {
+                self.speed = speed;
+                true
+            }
+            Msg::SetPrecision(precision) => {
+                self.precision = precision.clamp(0, MAX_PRECISION);
+                true
+            }
+            Msg::ToggleMiles => {
+                self.show_miles = !self.show_miles;
+                true
+            }
+            Msg::ToggleInfo => {
+                self.show_info = !self.show_info;
+                true
+            }
+            Msg::ToggleNormalize => {
+                self.normalize = !self.normalize;
                 true
             }
             Msg::GetDistance => {
-                let url = format!("http://localhost:5000/distance?src={}&dst={}", self.src, self.dst);
-                ctx.link().send_future(async {
-                    match fetch_distance(url).await {
-                        Ok(dist) => Msg::SetDistanceFetchState(FetchState::Success(dist)),
+                let stops = self.stops();
+                let unchanged = self.last_request.as_ref() == Some(&stops);
+                if unchanged && matches!(self.distance, FetchState::Success(_)) {
+                    return false;
+                }
+                self.last_request = Some(stops.clone());
+                let attempts = self.fetch_attempts.max(1);
+                let timeout_ms = self.fetch_timeout_ms;
+                if let Some(previous) = self.abort.take() {
+                    previous.abort();
+                }
+                self.abort = AbortController::new().ok();
+                let signal = self.abort.as_ref().map(AbortController::signal);
+                let link = ctx.link().clone();
+                ctx.link().send_future(async move {
+                    let on_attempt = |attempt| {
+                        link.send_message(Msg::SetDistanceFetchState(FetchState::Fetching { attempt }))
+                    };
+                    let result =
+                        fetch_route(stops.clone(), attempts, timeout_ms, signal.as_ref(), on_attempt).await;
+                    if signal.as_ref().is_some_and(AbortSignal::aborted) {
+                        return Msg::SetDistanceFetchState(FetchState::Failed(FetchError::Aborted));
+                    }
+                    match result {
+                        Ok(legs) => Msg::SetDistanceFetchState(FetchState::Success(Route::new(stops, legs))),
                         Err(err) => Msg::SetDistanceFetchState(FetchState::Failed(err)),
                     }
                 });
                 ctx.link()
-                    .send_message(Msg::SetDistanceFetchState(FetchState::Fetching));
+                    .send_message(Msg::SetDistanceFetchState(FetchState::Fetching { attempt: 1 }));
                 false
             }
+            Msg::Swap => {
+                std::mem::swap(&mut self.src, &mut self.dst);
+                self.waypoints.reverse();
+                // The reversed route has the same length, so the shown distance stays valid.
+                self.last_request = None;
+                true
+            }
+            Msg::Reset => {
+                if let Some(previous) = self.abort.take() {
+                    previous.abort();
+                }
+                self.src.clear();
+                self.dst.clear();
+                self.waypoints.clear();
+                self.last_request = None;
+                self.dirty = false;
+                self.distance = FetchState::NotFetching;
+                true
+            }
+            Msg::ToggleTheme => {
+                self.theme = self.theme.toggled();
+                self.theme.store();
+                true
+            }
+            Msg::Export => {
+                // A stale distance was computed for other coordinates than those shown.
+                if let (false, Some(meters)) = (self.is_stale(), self.total_distance()) {
+                    let export = Export {
+                        src: &self.src,
+                        waypoints: &self.waypoints,
+                        dst: &self.dst,
+                        distance: meters / METERS_PER_KM,
+                        unit: "km",
+                    };
+                    let filename = format!("distance-{}.json", chrono::Local::now().format("%Y-%m-%d"));
+                    let result = serde_json::to_string_pretty(&export)
+                        .map_err(|err| JsValue::from_str(&err.to_string()))
+                        .and_then(|contents| download_json(&filename, &contents));
+                    if let Err(err) = result {
+                        web_sys::console::error_1(&err);
+                        ctx.link().send_message(Msg::ShowToast("Couldn't export the result".to_string()));
+                    }
+                }
+                false
+            }
+            Msg::CopyLink | Msg::CopyPermalink => {
+                let with_result = matches!(msg, Msg::CopyPermalink);
+                if let Some(link) = self.share_link(with_result) {
+                    ctx.link().send_future(async move {
+                        match copy_to_clipboard(link).await {
+                            Ok(()) => Msg::ShowToast("Link copied to clipboard".to_string()),
+                            Err(err) => {
+                                web_sys::console::error_1(&err);
+                                Msg::ShowToast("Couldn't copy the link".to_string())
+                            }
+                        }
+                    });
+                }
+                false
+            }
+            Msg::ShowToast(message) => {
+                self.toast = Some(message);
+                let link = ctx.link().clone();
+                self.toast_timer = Some(Timeout::new(TOAST_MS, move || {
+                    link.send_message(Msg::DismissToast)
+                }));
+                true
+            }
+            Msg::DismissToast => {
+                self.toast = None;
+                self.toast_timer = None;
+                true
+            }
+            Msg::MapUnavailable => {
+                self.map_unavailable = true;
+                true
+            }
+            Msg::SetDistanceFetchState(FetchState::Failed(FetchError::Aborted)) => false,
             Msg::SetDistanceFetchState(fetch_state) => {
+                match &fetch_state {
+                    FetchState::Success(route) => {
+                        self.history.push(HistoryEntry {
+                            stops: route.stops.clone(),
+                            meters: route.meters,
+                        });
+                        if self.history.len() > HISTORY_LEN {
+                            self.history.remove(0);
+                        }
+                        // Inputs edited while the request was in flight leave it stale.
+                        self.dirty = route.stops != self.stops();
+                        self.map_outdated = true;
+                        self.just_updated = true;
+                        let link = ctx.link().clone();
+                        self.highlight = Some(Timeout::new(HIGHLIGHT_MS, move || {
+                            link.send_message(Msg::ClearHighlight)
+                        }));
+                    }
+                    FetchState::Failed(FetchError::Js(err)) => web_sys::console::error_1(err),
+                    _ => {}
+                }
                 self.distance = fetch_state;
                 true
             }
+            Msg::ClearHighlight => {
+                self.just_updated = false;
+                self.highlight = None;
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if !self.map_outdated {
+            return;
+        }
+        self.map_outdated = false;
+        if let (false, FetchState::Success(route)) = (self.map_unavailable, &self.distance) {
+            if !route.legs.is_empty() && !map::render_route(MAP_ID, &route.legs) {
+                ctx.link().send_message(Msg::MapUnavailable);
+            }
         }
     }
 
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        self.keydown.take();
+        self.highlight.take();
+        self.toast_timer.take();
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
         let on_change1 = ctx.link().callback(Msg::SetSrc);
         let on_change2 = ctx.link().callback(Msg::SetDst);
+        let on_speed = ctx.link().callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::SetSpeed(input.value().parse().unwrap_or(0.0))
+        });
+        let on_precision = ctx.link().callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::SetPrecision(input.value().parse().unwrap_or(DEFAULT_PRECISION))
+        });
         html! {
-            <main>
+            <main
+                class={self.theme.class()}
+                ondragover={Callback::from(|event: DragEvent| {
+                    if drags_files(&event) {
+                        event.prevent_default();
+                    }
+                })}
+                ondrop={load_dropped_file(ctx)}
+            >
+                <div>
+                    <button onclick={ctx.link().callback(|_| Msg::ToggleTheme)}>
+                        { match self.theme {
+                            Theme::Light => "Dark mode",
+                            Theme::Dark => "Light mode",
+                        } }
+                    </button>
+                </div>
                 <div class="entry">
                     <div>
-                        {"Enter SRC (lat, lng) or H3 index:"}
+                        {format!("Enter SRC ({}) or H3 index:", self.pair_order())}
+                        <div class="footnote" id="src-hint">
+                            {format!(
+                                "Example: {} OR 631246145620214271 OR 8c2a306638701ff",
+                                self.example_pair("42.3541165", "-71.0693514")
+                            )}
+                        </div>
                         <div class="footnote">
-                            {"Example: 42.3541165,-71.0693514 OR 631246145620214271 OR 8c2a306638701ff"}
+                            {"Or drop a text file with SRC on the first line and DST on the second."}
                         </div>
                     </div>
                     <div>
-                        <SrcInput {on_change1} value={self.src.clone()} />
+                        { if self.numeric_entry {
+                            self.view_numeric(ctx, &self.src, "Source", Msg::SetSrcPart)
+                        } else {
+                            html! {
+                                <SrcInput
+                                    {on_change1}
+                                    on_paste={ctx.link().callback(Msg::PasteSrc)}
+                                    value={self.src.clone()}
+                                    aria_label="Source coordinate"
+                                    aria_describedby={Some("src-hint".to_string())}
+                                    normalize={self.normalize}
+                                />
+                            }
+                        } }
+                        <PresetSelect
+                            on_select={ctx.link().callback(Msg::SetSrc)}
+                            lng_lat={self.lng_lat}
+                            aria_label="Source preset city"
+                        />
                     </div>
                     <br/>
+                    { for self.waypoints.iter().enumerate().map(|(i, wp)| self.view_waypoint(ctx, i, wp)) }
                     <div>
-                        {"Enter DST (lat, lng) or H3 index:"}
-                        <div class="footnote">
-                            {"Example: 40.7791472, -73.9680804 OR 631243921460311551 OR 8c2a100894435ff"}
+                        <button onclick={ctx.link().callback(|_| Msg::AddWaypoint)}>
+                            { "+ Add waypoint" }
+                        </button>
+                    </div>
+                    <br/>
+                    <div>
+                        {format!("Enter DST ({}) or H3 index:", self.pair_order())}
+                        <div class="footnote" id="dst-hint">
+                            {format!(
+                                "Example: {} OR 631243921460311551 OR 8c2a100894435ff",
+                                self.example_pair("40.7791472", "-73.9680804")
+                            )}
                         </div>
                     </div>
                     <div>
-                        <DstInput {on_change2} value={self.dst.clone()} />
+                        { if self.numeric_entry {
+                            self.view_numeric(ctx, &self.dst, "Destination", Msg::SetDstPart)
+                        } else {
+                            html! {
+                                <DstInput
+                                    {on_change2}
+                                    on_paste={ctx.link().callback(Msg::PasteDst)}
+                                    value={self.dst.clone()}
+                                    aria_label="Destination coordinate"
+                                    aria_describedby={Some("dst-hint".to_string())}
+                                    normalize={self.normalize}
+                                />
+                            }
+                        } }
+                        <PresetSelect
+                            on_select={ctx.link().callback(Msg::SetDst)}
+                            lng_lat={self.lng_lat}
+                            aria_label="Destination preset city"
+                        />
+                    </div>
+                    <br/>
+                    <div>
+                        {"Travel speed in km/h (optional):"}
+                    </div>
+                    <div>
+                        <input type="number" min="0" oninput={on_speed} aria-label="Travel speed in km/h" />
                     </div>
                     <br/>
+                    <div class="footnote">
+                        <label>
+                            <input
+                                type="checkbox"
+                                checked={self.normalize}
+                                onchange={ctx.link().callback(|_| Msg::ToggleNormalize)}
+                            />
+                            {" Tidy coordinates as I type (e.g. 42.35N, 71.06W)"}
+                        </label>
+                    </div>
+                    <div class="footnote">
+                        <label>
+                            <input
+                                type="checkbox"
+                                checked={self.numeric_entry}
+                                onchange={ctx.link().callback(|_| Msg::ToggleEntryMode)}
+                            />
+                            {" Enter SRC and DST as separate lat and lng numbers"}
+                        </label>
+                    </div>
+                    <div class="footnote">
+                        <label>
+                            <input
+                                type="checkbox"
+                                checked={self.lng_lat}
+                                onchange={ctx.link().callback(|_| Msg::ToggleOrder)}
+                            />
+                            {" Coordinates are in lng, lat order (GeoJSON, PostGIS)"}
+                        </label>
+                    </div>
                 </div>
                 <div>
-                    <button onclick={ctx.link().callback(|_| Msg::GetDistance)}>
+                    <button onclick={ctx.link().callback(|_| Msg::GetDistance)} title="Ctrl+Enter">
                         { "Submit" }
                     </button>
+                    <button onclick={ctx.link().callback(|_| Msg::Swap)} title="Alt+S">
+                        { "Swap" }
+                    </button>
+                    <button onclick={ctx.link().callback(|_| Msg::Reset)}>
+                        { "Reset" }
+                    </button>
+                    <button
+                        onclick={ctx.link().callback(|_| Msg::Export)}
+                        disabled={self.total_distance().is_none() || self.is_stale()}
+                    >
+                        { "Export" }
+                    </button>
+                    <button onclick={ctx.link().callback(|_| Msg::CopyLink)}>
+                        { "Copy link" }
+                    </button>
+                    <button
+                        onclick={ctx.link().callback(|_| Msg::CopyPermalink)}
+                        disabled={self.total_distance().is_none() || self.is_stale()}
+                    >
+                        { "Copy link with result" }
+                    </button>
                 </div>
-                <div class="readout">
-                    <div>
+                <div
+                    class={classes!(
+                        "readout",
+                        self.is_stale().then_some("stale"),
+                        self.just_updated.then_some("fresh")
+                    )}
+                >
+                    <div role="status" aria-live="polite">
+                        { if matches!(self.distance, FetchState::Fetching { .. }) {
+                            html! { <span class="spinner" aria-hidden="true"></span> }
+                        } else {
+                            html! {}
+                        } }
                         {self.row_text()}
                     </div>
+                    {self.view_legs()}
+                    { for self.eta_text().map(|eta| html! { <div>{eta}</div> }) }
+                    {self.view_history_chart()}
+                    {self.view_map()}
+                    <div class="footnote">
+                        {format!("Decimals: {} ", self.precision)}
+                        <input
+                            type="range"
+                            min="0"
+                            max={MAX_PRECISION.to_string()}
+                            value={self.precision.to_string()}
+                            oninput={on_precision}
+                            aria-label="Decimal places"
+                        />
+                    </div>
+                    <div class="footnote">
+                        <label>
+                            <input
+                                type="checkbox"
+                                checked={self.show_miles}
+                                onchange={ctx.link().callback(|_| Msg::ToggleMiles)}
+                            />
+                            {" Show miles too"}
+                        </label>
+                    </div>
                     <div class="footnote">
-                        {"NOTE* Calculating using vincenty algorithm"}
+                        {"NOTE* Calculating using vincenty algorithm "}
+                        <button
+                            class="info-toggle"
+                            onclick={ctx.link().callback(|_| Msg::ToggleInfo)}
+                            aria-expanded={self.show_info.to_string()}
+                            aria-label="About the Vincenty algorithm"
+                        >
+                            { "ⓘ" }
+                        </button>
                     </div>
+                    {self.view_info()}
                 </div>
+                { for self.toast.clone().map(|message| html! {
+                    <Toast {message} on_dismiss={ctx.link().callback(|_: ()| Msg::DismissToast)} />
+                }) }
             </main>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_input::normalize_input;
+
+    #[test]
+    fn split_pair_needs_exactly_four_numbers() {
+        assert_eq!(
+            split_pair("42.35,-71.06 40.77,-73.96"),
+            Some(("42.35,-71.06".to_string(), "40.77,-73.96".to_string()))
+        );
+        assert_eq!(
+            split_pair("42.35 -71.06; 40.77 -73.96"),
+            Some(("42.35,-71.06".to_string(), "40.77,-73.96".to_string()))
+        );
+        assert_eq!(split_pair("42.35,-71.06"), None);
+        assert_eq!(split_pair("42.35,-71.06 40.77"), None);
+        assert_eq!(split_pair("42.35,-71.06 40.77,-73.96 1.0"), None);
+        assert_eq!(split_pair("42.35,-71.06 8c2a100894435ff,1"), None);
+    }
+
+    #[test]
+    fn normalize_location_accepts_comma() {
+        assert_eq!(normalize_location("42.35,-71.06", false), "42.35,-71.06");
+        assert_eq!(normalize_location("42.35, -71.06", false), "42.35,-71.06");
+    }
+
+    #[test]
+    fn normalize_location_accepts_whitespace() {
+        assert_eq!(normalize_location("42.35 -71.06", false), "42.35,-71.06");
+        assert_eq!(normalize_location("42.35\t  -71.06", false), "42.35,-71.06");
+    }
+
+    #[test]
+    fn normalize_location_accepts_semicolon() {
+        assert_eq!(normalize_location("42.35;-71.06", false), "42.35,-71.06");
+        assert_eq!(normalize_location("42.35 ; -71.06", false), "42.35,-71.06");
+    }
+
+    #[test]
+    fn normalize_location_trims_surrounding_spaces() {
+        assert_eq!(normalize_location("  42.35,-71.06  ", false), "42.35,-71.06");
+    }
+
+    #[test]
+    fn normalize_location_resolves_both_orders_to_the_same_point() {
+        assert_eq!(
+            normalize_location("42.35,-71.06", false),
+            normalize_location("-71.06,42.35", true)
+        );
+        assert_eq!(
+            normalize_location("42.35 -71.06", false),
+            normalize_location("-71.06 42.35", true)
+        );
+        assert_eq!(normalize_location("8c2a306638701ff", true), "8c2a306638701ff");
+    }
+
+    #[test]
+    fn route_stops_resolve_mixed_inputs_independently() {
+        assert_eq!(
+            route_stops(" 42.35 -71.06", &[], "8c2a100894435ff ", false),
+            vec!["42.35,-71.06".to_string(), "8c2a100894435ff".to_string()]
+        );
+        // Tidying typed input must leave a hex H3 index intact next to a decimal pair.
+        assert_eq!(
+            route_stops(&normalize_input("42.35N -71.06"), &[], &normalize_input("8e2a1072b59a7ff"), false),
+            vec!["42.35,-71.06".to_string(), "8e2a1072b59a7ff".to_string()]
+        );
+        assert_eq!(
+            route_stops("631246145620214271", &["40.77;-73.96".to_string()], "-0.13,51.51", true),
+            vec![
+                "631246145620214271".to_string(),
+                "-73.96,40.77".to_string(),
+                "51.51,-0.13".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_location_passes_h3_through() {
+        assert_eq!(normalize_location(" 8c2a306638701ff ", false), "8c2a306638701ff");
+        assert_eq!(normalize_location("631246145620214271", false), "631246145620214271");
+    }
+}
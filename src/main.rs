@@ -1,7 +1,13 @@
 #![recursion_limit = "256"]
 
+mod map;
+
+mod presets;
+
 mod text_input;
 
+mod toast;
+
 mod app;
 
 use app::App;
@@ -0,0 +1,145 @@
+use crate::app::{Coordinate, InnerData};
+use wasm_bindgen::prelude::*;
+
+/// Number of points used to draw each leg of the route.
+const POINTS_PER_LEG: usize = 64;
+
+#[wasm_bindgen(inline_js = r#"
+export function render_route(id, points) {
+    if (typeof L === "undefined") {
+        return false;
+    }
+    const el = document.getElementById(id);
+    if (!el) {
+        return false;
+    }
+    if (!el._map) {
+        el._map = L.map(el);
+        L.tileLayer("https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png", {
+            attribution: "&copy; OpenStreetMap contributors",
+        }).addTo(el._map);
+        el._layer = L.layerGroup().addTo(el._map);
+    }
+    el._layer.clearLayers();
+    L.marker(points[0]).addTo(el._layer);
+    L.marker(points[points.length - 1]).addTo(el._layer);
+    const line = L.polyline(points).addTo(el._layer);
+    // The container may have been hidden since the map last measured it.
+    el._map.invalidateSize();
+    el._map.fitBounds(line.getBounds(), { padding: [16, 16] });
+    return true;
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = render_route)]
+    fn render_route_js(id: &str, points: &JsValue) -> Result<bool, JsValue>;
+}
+
+fn to_unit_vector(c: &Coordinate) -> [f64; 3] {
+    let (lat, lng) = (c.lat.to_radians(), c.lng.to_radians());
+    [lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin()]
+}
+
+/// `n` evenly spaced (lat, lng) points along the great circle from `a` to `b`.
+/// This is a spherical approximation, which is plenty for drawing on a map.
+fn great_circle_points(a: &Coordinate, b: &Coordinate, n: usize) -> Vec<(f64, f64)> {
+    let (p, q) = (to_unit_vector(a), to_unit_vector(b));
+    let dot = (p[0] * q[0] + p[1] * q[1] + p[2] * q[2]).clamp(-1.0, 1.0);
+    let omega = dot.acos();
+    // Coincident or antipodal points have no unique great circle.
+    if n < 2 || omega.sin().abs() < 1e-12 {
+        return vec![(a.lat, a.lng), (b.lat, b.lng)];
+    }
+    (0..n)
+        .map(|i| {
+            let t = i as f64 / (n - 1) as f64;
+            let wa = ((1.0 - t) * omega).sin() / omega.sin();
+            let wb = (t * omega).sin() / omega.sin();
+            let v = [
+                wa * p[0] + wb * q[0],
+                wa * p[1] + wb * q[1],
+                wa * p[2] + wb * q[2],
+            ];
+            let lat = v[2].atan2((v[0] * v[0] + v[1] * v[1]).sqrt());
+            let lng = v[1].atan2(v[0]);
+            (lat.to_degrees(), lng.to_degrees())
+        })
+        .collect()
+}
+
+/// The (lat, lng) points of every leg in order. Longitudes are kept continuous, so they
+/// may leave -180..180, letting antimeridian crossings draw without wrapping around the map.
+fn route_points(legs: &[InnerData]) -> Vec<(f64, f64)> {
+    let mut points: Vec<(f64, f64)> = Vec::with_capacity(legs.len() * POINTS_PER_LEG);
+    for leg in legs {
+        for (lat, mut lng) in great_circle_points(&leg.src, &leg.dst, POINTS_PER_LEG) {
+            if let Some(&(_, prev)) = points.last() {
+                lng += 360.0 * ((prev - lng) / 360.0).round();
+            }
+            points.push((lat, lng));
+        }
+    }
+    points
+}
+
+/// Plot the route's endpoints and great-circle line into the element with `id`.
+/// Returns false if the map library is unavailable, so the caller can hide the map.
+pub fn render_route(id: &str, legs: &[InnerData]) -> bool {
+    let points: js_sys::Array = route_points(legs)
+        .into_iter()
+        .map(|(lat, lng)| js_sys::Array::of2(&lat.into(), &lng.into()))
+        .collect();
+    if points.length() == 0 {
+        return false;
+    }
+    render_route_js(id, &points).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coord(lat: f64, lng: f64) -> Coordinate {
+        Coordinate { lat, lng }
+    }
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn great_circle_points_start_and_end_at_the_inputs() {
+        let points = great_circle_points(&coord(42.35, -71.06), &coord(51.51, -0.13), POINTS_PER_LEG);
+        assert_eq!(points.len(), POINTS_PER_LEG);
+        assert_close(points[0], (42.35, -71.06));
+        assert_close(points[POINTS_PER_LEG - 1], (51.51, -0.13));
+    }
+
+    #[test]
+    fn great_circle_points_fall_back_to_the_endpoints() {
+        let (a, b) = (coord(42.35, -71.06), coord(51.51, -0.13));
+        assert_eq!(great_circle_points(&a, &b, 1), vec![(42.35, -71.06), (51.51, -0.13)]);
+        assert_eq!(great_circle_points(&a, &a, POINTS_PER_LEG), vec![(42.35, -71.06), (42.35, -71.06)]);
+        let (c, d) = (coord(0.0, 0.0), coord(0.0, 180.0));
+        assert_eq!(great_circle_points(&c, &d, POINTS_PER_LEG), vec![(0.0, 0.0), (0.0, 180.0)]);
+    }
+
+    #[test]
+    fn route_points_stay_continuous_across_the_antimeridian() {
+        // Suva, Fiji to Apia, Samoa.
+        let leg = InnerData {
+            distance: 1145.0,
+            src: coord(-18.1416, 178.4419),
+            dst: coord(-13.8333, -171.7667),
+        };
+        let points = route_points(&[leg]);
+        assert!(points.windows(2).all(|pair| (pair[1].1 - pair[0].1).abs() < 1.0));
+        assert_close(points[0], (-18.1416, 178.4419));
+        assert_close(points[points.len() - 1], (-13.8333, -171.7667 + 360.0));
+    }
+}
@@ -0,0 +1,9 @@
+/// Well-known cities offered as ready-made coordinates, as `(name, lat, lng)`.
+pub const PRESETS: [(&str, f64, f64); 6] = [
+    ("Boston", 42.3541165, -71.0693514),
+    ("NYC", 40.7791472, -73.9680804),
+    ("London", 51.5072178, -0.1275862),
+    ("Tokyo", 35.6761919, 139.6503106),
+    ("Suva, Fiji", -18.1248086, 178.4500789),
+    ("Apia, Samoa", -13.8506958, -171.7513551),
+];
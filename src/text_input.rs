@@ -1,54 +1,262 @@
-use wasm_bindgen::JsCast;
-use wasm_bindgen::UnwrapThrowExt;
 use web_sys::Event;
 use web_sys::HtmlInputElement;
+use web_sys::HtmlSelectElement;
 use web_sys::InputEvent;
 use yew::prelude::*;
 
+use crate::presets::PRESETS;
+
 #[derive(Clone, PartialEq, Properties)]
 pub struct SrcProps {
     pub value: String,
     pub on_change1: Callback<String>,
+    /// Receives pasted values instead of `on_change1` when set.
+    #[prop_or_default]
+    pub on_paste: Option<Callback<String>>,
+    pub aria_label: String,
+    #[prop_or_default]
+    pub aria_describedby: Option<String>,
+    #[prop_or_default]
+    pub normalize: bool,
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct DstProps {
     pub value: String,
     pub on_change2: Callback<String>,
+    /// Receives pasted values instead of `on_change2` when set.
+    #[prop_or_default]
+    pub on_paste: Option<Callback<String>>,
+    pub aria_label: String,
+    #[prop_or_default]
+    pub aria_describedby: Option<String>,
+    #[prop_or_default]
+    pub normalize: bool,
+}
+
+/// Turn a number with a trailing hemisphere letter ("42.35N", "71.06W") into a signed
+/// number. Anything else, including H3 indexes, is returned untouched. `east` allows the
+/// "E" suffix, which is also a hex digit.
+fn apply_hemisphere(token: &str, east: bool) -> String {
+    let (number, sign) = match token.char_indices().last() {
+        Some((i, 'N' | 'n')) => (&token[..i], ""),
+        Some((i, 'E' | 'e')) if east => (&token[..i], ""),
+        Some((i, 'S' | 's' | 'W' | 'w')) => (&token[..i], "-"),
+        _ => return token.to_string(),
+    };
+    match number.parse::<f64>() {
+        Ok(degrees) if !number.starts_with('-') && degrees <= 180.0 => format!("{}{}", sign, number),
+        _ => token.to_string(),
+    }
 }
 
-fn get_value_from_input_event(e: InputEvent) -> String {
-    let event: Event = e.dyn_into().unwrap_throw();
-    let event_target = event.target().unwrap_throw();
-    let target: HtmlInputElement = event_target.dyn_into().unwrap_throw();
-    web_sys::console::log_1(&target.value().into());
-    target.value()
+/// Conservatively tidy a typed coordinate: drop leading whitespace, collapse repeated
+/// whitespace and commas, and apply hemisphere letters. Only tokens closed by a separator
+/// are rewritten, so the one being typed is left alone, and trailing separators are kept
+/// so the user can carry on typing.
+pub(crate) fn normalize_input(value: &str) -> String {
+    let value = value.trim_start();
+    // A lone hex token, such as an H3 index, may contain an "e" that isn't East.
+    let east = !value.trim_end().chars().all(|c| c.is_ascii_hexdigit());
+    let mut normalized = String::with_capacity(value.len());
+    let mut token = String::new();
+    for c in value.chars() {
+        if c == ',' || c.is_whitespace() {
+            normalized.push_str(&apply_hemisphere(&token, east));
+            token.clear();
+            let separator = if c == ',' { ',' } else { ' ' };
+            if !normalized.ends_with(separator) {
+                normalized.push(separator);
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    normalized.push_str(&token);
+    normalized
+}
+
+/// Whether the input came from pasting rather than typing.
+fn is_paste(e: &InputEvent) -> bool {
+    e.input_type() == "insertFromPaste"
+}
+
+fn get_value_from_input_event(e: InputEvent, normalize: bool) -> String {
+    let value = e
+        .target_dyn_into::<HtmlInputElement>()
+        .map(|target| target.value())
+        .unwrap_or_default();
+    if normalize {
+        normalize_input(&value)
+    } else {
+        value
+    }
 }
 
 /// Controlled Text Input Component
 #[function_component(SrcInput)]
 pub fn src_input(props: &SrcProps) -> Html {
-    let SrcProps { value, on_change1 } = props.clone();
+    let SrcProps {
+        value,
+        on_change1,
+        on_paste,
+        aria_label,
+        aria_describedby,
+        normalize,
+    } = props.clone();
 
     let oninput = Callback::from(move |input_event: InputEvent| {
-        on_change1.emit(get_value_from_input_event(input_event));
+        let callback = match &on_paste {
+            Some(on_paste) if is_paste(&input_event) => on_paste,
+            _ => &on_change1,
+        };
+        callback.emit(get_value_from_input_event(input_event, normalize));
     });
 
     html! {
-        <input type="text" {value} {oninput} />
+        <input
+            type="text"
+            {value}
+            {oninput}
+            aria-label={aria_label}
+            aria-describedby={aria_describedby}
+        />
     }
 }
 
 /// Controlled Text Input Component
 #[function_component(DstInput)]
 pub fn dst_input(props: &DstProps) -> Html {
-    let DstProps { value, on_change2 } = props.clone();
+    let DstProps {
+        value,
+        on_change2,
+        on_paste,
+        aria_label,
+        aria_describedby,
+        normalize,
+    } = props.clone();
 
     let oninput = Callback::from(move |input_event: InputEvent| {
-        on_change2.emit(get_value_from_input_event(input_event));
+        let callback = match &on_paste {
+            Some(on_paste) if is_paste(&input_event) => on_paste,
+            _ => &on_change2,
+        };
+        callback.emit(get_value_from_input_event(input_event, normalize));
     });
 
     html! {
-        <input type="text" {value} {oninput} />
+        <input
+            type="text"
+            {value}
+            {oninput}
+            aria-label={aria_label}
+            aria-describedby={aria_describedby}
+        />
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct WaypointProps {
+    pub value: String,
+    pub on_change: Callback<String>,
+    pub aria_label: String,
+    #[prop_or_default]
+    pub aria_describedby: Option<String>,
+    #[prop_or_default]
+    pub normalize: bool,
+}
+
+/// Controlled Text Input Component
+#[function_component(WaypointInput)]
+pub fn waypoint_input(props: &WaypointProps) -> Html {
+    let WaypointProps {
+        value,
+        on_change,
+        aria_label,
+        aria_describedby,
+        normalize,
+    } = props.clone();
+
+    let oninput = Callback::from(move |input_event: InputEvent| {
+        on_change.emit(get_value_from_input_event(input_event, normalize));
+    });
+
+    html! {
+        <input
+            type="text"
+            {value}
+            {oninput}
+            aria-label={aria_label}
+            aria-describedby={aria_describedby}
+        />
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct PresetProps {
+    pub on_select: Callback<String>,
+    pub aria_label: String,
+    /// Emit "lng,lat" instead of "lat,lng".
+    #[prop_or_default]
+    pub lng_lat: bool,
+}
+
+/// Dropdown of preset cities, emitting the chosen city as "lat,lng" or "lng,lat"
+#[function_component(PresetSelect)]
+pub fn preset_select(props: &PresetProps) -> Html {
+    let PresetProps {
+        on_select,
+        aria_label,
+        lng_lat,
+    } = props.clone();
+
+    let onchange = Callback::from(move |event: Event| {
+        let target: HtmlSelectElement = event.target_unchecked_into();
+        let selected = PRESETS.iter().find(|(name, _, _)| *name == target.value());
+        if let Some((_, lat, lng)) = selected {
+            if lng_lat {
+                on_select.emit(format!("{},{}", lng, lat));
+            } else {
+                on_select.emit(format!("{},{}", lat, lng));
+            }
+        }
+        // Back to the placeholder, so picking the same city again still fires a change.
+        target.set_selected_index(0);
+    });
+
+    html! {
+        <select {onchange} aria-label={aria_label}>
+            <option value="" selected={true} disabled={true}>{"Presets..."}</option>
+            { for PRESETS.iter().map(|(name, _, _)| html! {
+                <option value={*name}>{*name}</option>
+            }) }
+        </select>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `input` one keystroke at a time, normalizing after each like the inputs do.
+    fn type_out(input: &str) -> String {
+        input.chars().fold(String::new(), |mut value, c| {
+            value.push(c);
+            normalize_input(&value)
+        })
+    }
+
+    #[test]
+    fn normalize_input_leaves_typed_hex_h3_alone() {
+        assert_eq!(type_out("8e2a1072b59a7ff"), "8e2a1072b59a7ff");
+        assert_eq!(type_out("8c2a306638701ff"), "8c2a306638701ff");
+        assert_eq!(normalize_input("8e "), "8e ");
+    }
+
+    #[test]
+    fn normalize_input_applies_hemispheres_to_closed_tokens() {
+        assert_eq!(type_out("42.35N 71.06W "), "42.35 -71.06 ");
+        assert_eq!(type_out("42.35S, 71.06E,"), "-42.35, 71.06,");
+        assert_eq!(type_out("42.35N"), "42.35N");
     }
 }
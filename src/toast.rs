@@ -0,0 +1,21 @@
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ToastProps {
+    pub message: String,
+    pub on_dismiss: Callback<()>,
+}
+
+/// Transient notification, dismissed early by clicking it
+#[function_component(Toast)]
+pub fn toast(props: &ToastProps) -> Html {
+    let ToastProps { message, on_dismiss } = props.clone();
+
+    let onclick = Callback::from(move |_: MouseEvent| on_dismiss.emit(()));
+
+    html! {
+        <div class="toast" role="status" aria-live="polite" {onclick}>
+            {message}
+        </div>
+    }
+}